@@ -7,13 +7,59 @@
 /// - Self-contained C FFI types and functions
 
 use std::ffi::CString;
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_ulong};
 use std::ptr;
+use std::sync::Mutex;
 
 // Self-contained C FFI types using standard library
 pub type CChar = c_char;
 pub type CInt = c_int;
 
+// Raw `ioctl` declaration - no libc crate, matches the one call shape we need
+extern "C" {
+    fn ioctl(fd: CInt, request: c_ulong, argp: *mut u8) -> CInt;
+}
+
+/// `KDGKBTYPE` - query keyboard/console type, used to confirm `fd` is a Linux virtual console
+const KDGKBTYPE: c_ulong = 0x4B33;
+/// `PIO_CMAP` - install a 16-entry RGB palette on a Linux virtual console
+const PIO_CMAP: c_ulong = 0x4B71;
+
+/// Standard 16-color VGA console palette, used as the default for slots the theme doesn't set
+const VGA16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (170, 0, 0),
+    (0, 170, 0),
+    (170, 85, 0),
+    (0, 0, 170),
+    (170, 0, 170),
+    (0, 170, 170),
+    (170, 170, 170),
+    (85, 85, 85),
+    (255, 85, 85),
+    (85, 255, 85),
+    (255, 255, 85),
+    (85, 85, 255),
+    (255, 85, 255),
+    (85, 255, 255),
+    (255, 255, 255),
+];
+
+/// Map a theme's SGR parameter string (e.g. "32" or "01;33") to a VGA16 palette index
+fn ansi_palette_index(param: &str) -> usize {
+    for part in param.split(';') {
+        if let Ok(code) = part.parse::<u32>() {
+            if (30..=37).contains(&code) {
+                return (code - 30) as usize;
+            }
+            if (90..=97).contains(&code) {
+                return (code - 90) as usize + 8;
+            }
+        }
+    }
+    7 // Default: white
+}
+
 /// Highlight scheme types - completely self-contained
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -21,23 +67,217 @@ pub enum HighlightScheme {
     Nano = 0,
     Vim = 1,
     Default = 2,
+    Rainbow = 3,
 }
 
-/// Pure Rust ANSI color codes - no external dependencies
+/// Reset code - not themed, always ends a styled run
 const COLOR_RESET: &str = "\x1b[0m";
-const COLOR_COMMENT: &str = "\x1b[32m";      // Green
-const COLOR_STRING: &str = "\x1b[33m";       // Yellow  
-const COLOR_KEYWORD: &str = "\x1b[34m";      // Blue
-const COLOR_VARIABLE: &str = "\x1b[36m";     // Cyan
-const COLOR_OPERATOR: &str = "\x1b[35m";     // Magenta
+
+/// A runtime-configurable set of SGR parameter strings, one per token category,
+/// the way a dircolors database maps keys to `LS_COLORS`-style SGR sequences
+#[derive(Debug, Clone)]
+struct Theme {
+    comment: String,
+    string: String,
+    keyword: String,
+    variable: String,
+    operator: String,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            comment: "32".to_string(),  // Green
+            string: "33".to_string(),   // Yellow
+            keyword: "34".to_string(),  // Blue
+            variable: "36".to_string(), // Cyan
+            operator: "35".to_string(), // Magenta
+        }
+    }
+}
+
+/// The currently loaded theme, if `rust_load_theme_from_dircolors` has been called successfully
+static ACTIVE_THEME: Mutex<Option<Theme>> = Mutex::new(None);
+
+/// Snapshot of the active theme, falling back to the built-in defaults
+fn current_theme() -> Theme {
+    ACTIVE_THEME.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// Build an SGR escape sequence from a theme's parameter string (e.g. "32" or "01;33")
+fn sgr(params: &str) -> String {
+    format!("\x1b[{}m", params)
+}
+
+/// Validate a dircolors-style SGR value: semicolon-separated, all-digit segments
+fn validate_sgr_value(value: &str) -> bool {
+    if value.is_empty() {
+        return false;
+    }
+    value
+        .split(';')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Parse a dircolors-format database (`KEY value` lines) into a `Theme`, starting from the
+/// currently active theme so unspecified keys keep their previous value
+fn parse_dircolors(data: &str) -> Option<Theme> {
+    let mut theme = current_theme();
+
+    for line in data.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = trimmed.splitn(2, char::is_whitespace);
+        let key = parts.next().unwrap_or("");
+        let value = parts.next().unwrap_or("").trim();
+
+        match key {
+            "COMMENT" | "STRING" | "KEYWORD" | "VARIABLE" | "OPERATOR" => {
+                if !validate_sgr_value(value) {
+                    return None; // Malformed value - leave the active theme untouched
+                }
+                match key {
+                    "COMMENT" => theme.comment = value.to_string(),
+                    "STRING" => theme.string = value.to_string(),
+                    "KEYWORD" => theme.keyword = value.to_string(),
+                    "VARIABLE" => theme.variable = value.to_string(),
+                    "OPERATOR" => theme.operator = value.to_string(),
+                    _ => unreachable!(),
+                }
+            }
+            _ => {} // Unknown keys are ignored
+        }
+    }
+
+    Some(theme)
+}
+
+/// Load a theme from a dircolors-format database, replacing the active theme on success
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_load_theme_from_dircolors(data: *const CChar, len: CInt) -> CInt {
+    if data.is_null() || len <= 0 {
+        return -1;
+    }
+
+    let slice = std::slice::from_raw_parts(data as *const u8, len as usize);
+    let text = match std::str::from_utf8(slice) {
+        Ok(s) => s,
+        Err(_) => return -1,
+    };
+
+    match parse_dircolors(text) {
+        Some(theme) => {
+            *ACTIVE_THEME.lock().unwrap() = Some(theme);
+            0
+        }
+        None => -1,
+    }
+}
 
 /// Convert C highlight scheme to color intensity
 fn scheme_to_intensity(scheme: HighlightScheme) -> bool {
     match scheme {
         HighlightScheme::Nano => false,    // Dim colors
-        HighlightScheme::Vim => true,      // Bright colors  
+        HighlightScheme::Vim => true,      // Bright colors
         HighlightScheme::Default => false, // Dim colors
+        HighlightScheme::Rainbow => true,  // Bright colors
+    }
+}
+
+/// Whether the scheme wants per-variable rainbow coloring instead of the theme's fixed variable color
+fn scheme_is_rainbow(scheme: HighlightScheme) -> bool {
+    matches!(scheme, HighlightScheme::Rainbow)
+}
+
+/// FNV-1a hash - small, deterministic, no external dependencies
+fn fnv1a_hash(bytes: &[u8]) -> u32 {
+    const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for &byte in bytes {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
     }
+    hash
+}
+
+/// Single xorshift32 step - used to spread one hash seed into several pseudo-random values
+fn xorshift32(state: u32) -> u32 {
+    let mut x = if state == 0 { 1 } else { state };
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x
+}
+
+/// Convert HSL (h in 0..360, s/l in 0..100) to 8-bit RGB
+fn hsl_to_rgb(h: u32, s: u32, l: u32) -> (u8, u8, u8) {
+    let h = h as f64 / 360.0;
+    let s = s as f64 / 100.0;
+    let l = l as f64 / 100.0;
+
+    if s == 0.0 {
+        let gray = (l * 255.0).round() as u8;
+        return (gray, gray, gray);
+    }
+
+    let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+    let p = 2.0 * l - q;
+
+    let hue_to_rgb = |p: f64, q: f64, mut t: f64| -> f64 {
+        if t < 0.0 {
+            t += 1.0;
+        }
+        if t > 1.0 {
+            t -= 1.0;
+        }
+        if t < 1.0 / 6.0 {
+            return p + (q - p) * 6.0 * t;
+        }
+        if t < 1.0 / 2.0 {
+            return q;
+        }
+        if t < 2.0 / 3.0 {
+            return p + (q - p) * (2.0 / 3.0 - t) * 6.0;
+        }
+        p
+    };
+
+    let r = hue_to_rgb(p, q, h + 1.0 / 3.0);
+    let g = hue_to_rgb(p, q, h);
+    let b = hue_to_rgb(p, q, h - 1.0 / 3.0);
+
+    (
+        (r * 255.0).round() as u8,
+        (g * 255.0).round() as u8,
+        (b * 255.0).round() as u8,
+    )
+}
+
+/// Deterministic per-variable-name color: same name always maps to the same RGB, in and across lines
+fn rainbow_color_for_variable(name: &str) -> (u8, u8, u8) {
+    let seed = fnv1a_hash(name.as_bytes());
+    let seed2 = xorshift32(seed);
+    let seed3 = xorshift32(seed2);
+
+    let h = seed % 361;
+    let s = 42 + seed2 % 57;
+    let l = 40 + seed3 % 30;
+
+    hsl_to_rgb(h, s, l)
+}
+
+/// 24-bit truecolor SGR sequence for a given variable name's deterministic rainbow color
+fn rainbow_sgr(name: &str) -> String {
+    let (r, g, b) = rainbow_color_for_variable(name);
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
 }
 
 /// Main highlighting function - completely self-contained
@@ -73,32 +313,275 @@ pub unsafe extern "C" fn rust_highlight_shell_script(
     }
 }
 
+/// HTML span output mode - same comment/string/variable/keyword/operator rules as
+/// `rust_highlight_shell_script`, markup instead of ANSI. Heredoc bodies and `$(...)`/backtick
+/// command substitutions get no special treatment here.
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+/// Uses only core Rust features and libcore/liballoc
+#[no_mangle]
+pub unsafe extern "C" fn rust_highlight_shell_script_html(
+    script_content: *const CChar,
+    script_len: CInt,
+) -> *mut CChar {
+    // Null pointer check
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    // Convert C string to Rust string slice
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let highlighted = highlight_script_internal_html(script_str);
+
+    match CString::new(highlighted) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Default stylesheet matching the CSS classes emitted by `rust_highlight_shell_script_html`
+///
+/// # Safety
+/// Returns pointer to static string - no allocation needed
+#[no_mangle]
+pub extern "C" fn rust_get_default_stylesheet() -> *const CChar {
+    b"pre { color: inherit; background: inherit; }\n\
+      .comment { color: #2ca02c; }\n\
+      .string { color: #d6b656; }\n\
+      .keyword { color: #1f6feb; }\n\
+      .variable { color: #17a2b8; }\n\
+      .operator { color: #a64dff; }\n\0"
+        .as_ptr() as *const CChar
+}
+
+/// State of an open heredoc body, carried across `script_content.lines()` iterations
+struct HeredocState {
+    delimiter: String,
+    quoted: bool,     // Quoted delimiter (e.g. `<<'EOF'`) disables variable expansion coloring
+    strip_tabs: bool, // `<<-` form: leading tabs are stripped before comparing to the delimiter
+}
+
+/// Scan `line` for a `<<` that is not inside a single/double-quoted string and not inside an
+/// arithmetic expansion (`$((...))` or bare `((...))`, where `<<` is the left-shift operator
+/// rather than a heredoc redirect). Returns the byte index of the `<<`, if any.
+fn find_heredoc_marker(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut arith_depth = 0i32;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+
+        if in_single {
+            if c == '\'' {
+                in_single = false;
+            }
+            i += 1;
+            continue;
+        }
+        if in_double {
+            if c == '\\' && i + 1 < bytes.len() {
+                i += 2;
+                continue;
+            }
+            if c == '"' {
+                in_double = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if c == '\'' {
+            in_single = true;
+            i += 1;
+            continue;
+        }
+        if c == '"' {
+            in_double = true;
+            i += 1;
+            continue;
+        }
+
+        if c == '(' && i + 1 < bytes.len() && bytes[i + 1] as char == '(' {
+            arith_depth += 1;
+            i += 2;
+            continue;
+        }
+        if c == ')' && i + 1 < bytes.len() && bytes[i + 1] as char == ')' && arith_depth > 0 {
+            arith_depth -= 1;
+            i += 2;
+            continue;
+        }
+
+        if c == '<' && i + 1 < bytes.len() && bytes[i + 1] as char == '<' && arith_depth == 0 {
+            return Some(i);
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Detect a heredoc opener (`<<EOF`, `<<-'EOF'`, `<<"EOF"`, …) on a line, if any.
+/// Deliberately does not treat `<<<` (here-string) as a heredoc opener, and ignores `<<`
+/// found inside string literals or arithmetic expansion (`$(( 1 << 2 ))`), where it is not
+/// a heredoc redirect at all.
+fn find_heredoc_opener(line: &str) -> Option<HeredocState> {
+    let idx = find_heredoc_marker(line)?;
+    let after = &line[idx + 2..];
+
+    if after.starts_with('<') {
+        return None; // Here-string, not a heredoc
+    }
+
+    let mut chars = after.chars().peekable();
+    let mut strip_tabs = false;
+    if chars.peek() == Some(&'-') {
+        strip_tabs = true;
+        chars.next();
+    }
+
+    while chars.peek() == Some(&' ') || chars.peek() == Some(&'\t') {
+        chars.next();
+    }
+
+    let mut quoted = false;
+    let mut quote_char = '\0';
+    if let Some(&c) = chars.peek() {
+        if c == '"' || c == '\'' {
+            quoted = true;
+            quote_char = c;
+            chars.next();
+        }
+    }
+
+    let mut delimiter = String::new();
+    for c in chars {
+        if quoted {
+            if c == quote_char {
+                break;
+            }
+        } else if !(c.is_alphanumeric() || c == '_') {
+            break;
+        }
+        delimiter.push(c);
+    }
+
+    if delimiter.is_empty() {
+        return None;
+    }
+
+    Some(HeredocState {
+        delimiter,
+        quoted,
+        strip_tabs,
+    })
+}
+
+/// Whether `line` is the terminator for an open heredoc
+fn is_heredoc_terminator(line: &str, state: &HeredocState) -> bool {
+    let candidate = if state.strip_tabs {
+        line.trim_start_matches('\t')
+    } else {
+        line
+    };
+    candidate == state.delimiter
+}
+
+/// Color a heredoc body line: string-colored throughout, with `$variable` expansion still
+/// highlighted unless the opening delimiter was quoted (which disables expansion)
+fn highlight_heredoc_line(line: &str, theme: &Theme, rainbow: bool, expand_vars: bool) -> String {
+    if !expand_vars {
+        return format!("{}{}{}", sgr(&theme.string), line, COLOR_RESET);
+    }
+
+    let mut result = String::new();
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    result.push_str(&sgr(&theme.string));
+    while i < chars.len() {
+        if chars[i] == '$' {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '{' || chars[i] == '}')
+            {
+                i += 1;
+            }
+            let variable: String = chars[start..i].iter().collect();
+            result.push_str(COLOR_RESET);
+            if rainbow {
+                result.push_str(&rainbow_sgr(&variable));
+            } else {
+                result.push_str(&sgr(&theme.variable));
+            }
+            result.push_str(&variable);
+            result.push_str(COLOR_RESET);
+            result.push_str(&sgr(&theme.string));
+        } else {
+            result.push(chars[i]);
+            i += 1;
+        }
+    }
+    result.push_str(COLOR_RESET);
+
+    result
+}
+
 /// Internal highlighting using pure Rust pattern matching
 fn highlight_script_internal(script_content: &str, scheme: HighlightScheme) -> String {
     let intense = scheme_to_intensity(scheme);
+    let rainbow = scheme_is_rainbow(scheme);
+    let theme = current_theme();
     let mut result = String::new();
-    
+    let mut heredoc: Option<HeredocState> = None;
+
     for line in script_content.lines() {
-        let highlighted_line = highlight_line_simple(line, intense);
+        if let Some(state) = &heredoc {
+            if is_heredoc_terminator(line, state) {
+                result.push_str(line);
+                heredoc = None;
+            } else {
+                result.push_str(&highlight_heredoc_line(line, &theme, rainbow, !state.quoted));
+            }
+            result.push('\n');
+            continue;
+        }
+
+        let highlighted_line = highlight_line_simple(line, intense, rainbow, &theme);
         result.push_str(&highlighted_line);
         result.push('\n');
+
+        if let Some(state) = find_heredoc_opener(line) {
+            heredoc = Some(state);
+        }
     }
-    
+
     result
 }
 
 /// Simple line highlighting using fundamental Rust features only
-fn highlight_line_simple(line: &str, _intense: bool) -> String {
+fn highlight_line_simple(line: &str, _intense: bool, rainbow: bool, theme: &Theme) -> String {
     let mut result = String::new();
     let chars: Vec<char> = line.chars().collect();
     let mut i = 0;
-    
+
     while i < chars.len() {
         let ch = chars[i];
-        
+
         // Comments
         if ch == '#' {
-            result.push_str(COLOR_COMMENT);
+            result.push_str(&sgr(&theme.comment));
             result.push_str(&line[i..]);
             result.push_str(COLOR_RESET);
             break;
@@ -106,7 +589,7 @@ fn highlight_line_simple(line: &str, _intense: bool) -> String {
         // String literals
         else if ch == '"' || ch == '\'' {
             let quote = ch;
-            result.push_str(COLOR_STRING);
+            result.push_str(&sgr(&theme.string));
             result.push(ch);
             i += 1;
             
@@ -129,17 +612,67 @@ fn highlight_line_simple(line: &str, _intense: bool) -> String {
             }
             result.push_str(COLOR_RESET);
         }
+        // Command substitution: $(...) - recursively tokenize the inner text as shell
+        else if ch == '$' && i + 1 < chars.len() && chars[i + 1] == '(' {
+            let mut depth = 0;
+            let mut j = i + 1;
+            while j < chars.len() {
+                if chars[j] == '(' {
+                    depth += 1;
+                } else if chars[j] == ')' {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                j += 1;
+            }
+            let terminated = depth == 0 && j < chars.len();
+            let inner_end = if terminated { j } else { chars.len() };
+            let inner: String = chars[i + 2..inner_end].iter().collect();
+
+            result.push_str("$(");
+            result.push_str(&highlight_line_simple(&inner, _intense, rainbow, theme));
+            if terminated {
+                result.push(')');
+            }
+            i = if terminated { j + 1 } else { chars.len() };
+            continue;
+        }
+        // Backtick command substitution - recursively tokenize the inner text as shell
+        else if ch == '`' {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j] != '`' {
+                j += 1;
+            }
+            let terminated = j < chars.len();
+            let inner: String = chars[i + 1..j].iter().collect();
+
+            result.push('`');
+            result.push_str(&highlight_line_simple(&inner, _intense, rainbow, theme));
+            if terminated {
+                result.push('`');
+            }
+            i = if terminated { j + 1 } else { chars.len() };
+            continue;
+        }
         // Variables
         else if ch == '$' {
-            result.push_str(COLOR_VARIABLE);
-            result.push(ch);
+            let start = i;
             i += 1;
-            
+
             // Collect variable name
             while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '{' || chars[i] == '}') {
-                result.push(chars[i]);
                 i += 1;
             }
+
+            let variable: String = chars[start..i].iter().collect();
+            if rainbow {
+                result.push_str(&rainbow_sgr(&variable));
+            } else {
+                result.push_str(&sgr(&theme.variable));
+            }
+            result.push_str(&variable);
             result.push_str(COLOR_RESET);
             continue;
         }
@@ -149,10 +682,10 @@ fn highlight_line_simple(line: &str, _intense: bool) -> String {
             while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
                 i += 1;
             }
-            
+
             let word: String = chars[start..i].iter().collect();
             if is_shell_keyword(&word) {
-                result.push_str(COLOR_KEYWORD);
+                result.push_str(&sgr(&theme.keyword));
                 result.push_str(&word);
                 result.push_str(COLOR_RESET);
             } else {
@@ -162,7 +695,7 @@ fn highlight_line_simple(line: &str, _intense: bool) -> String {
         }
         // Operators
         else if "=<>!&|".contains(ch) {
-            result.push_str(COLOR_OPERATOR);
+            result.push_str(&sgr(&theme.operator));
             result.push(ch);
             result.push_str(COLOR_RESET);
         }
@@ -177,6 +710,131 @@ fn highlight_line_simple(line: &str, _intense: bool) -> String {
     result
 }
 
+/// CSS class names for HTML output - stable, one per token category
+const CSS_CLASS_COMMENT: &str = "comment";
+const CSS_CLASS_STRING: &str = "string";
+const CSS_CLASS_KEYWORD: &str = "keyword";
+const CSS_CLASS_VARIABLE: &str = "variable";
+const CSS_CLASS_OPERATOR: &str = "operator";
+
+/// Escape the characters that are meaningful in HTML text content
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Wrap HTML-escaped text in a `<span class="...">` for the given token category
+fn html_span(class: &str, text: &str) -> String {
+    format!("<span class=\"{}\">{}</span>", class, html_escape(text))
+}
+
+/// HTML span rendering of a script, line by line, with HTML markup instead of ANSI.
+/// Unlike `highlight_script_internal`, no heredoc state is carried across lines.
+fn highlight_script_internal_html(script_content: &str) -> String {
+    let mut result = String::from("<pre>\n");
+
+    for line in script_content.lines() {
+        result.push_str(&highlight_line_html(line));
+        result.push('\n');
+    }
+
+    result.push_str("</pre>");
+    result
+}
+
+/// Line highlighter mirroring `highlight_line_simple`'s comment/string/variable/keyword/operator
+/// rules, emitting HTML spans instead of SGR codes. `$(...)` and backtick substitutions are not
+/// given special handling: the `$` becomes a variable span and the enclosed text goes through
+/// the same rules as the rest of the line.
+fn highlight_line_html(line: &str) -> String {
+    let mut result = String::new();
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+
+        // Comments
+        if ch == '#' {
+            let rest: String = chars[i..].iter().collect();
+            result.push_str(&html_span(CSS_CLASS_COMMENT, &rest));
+            break;
+        }
+        // String literals
+        else if ch == '"' || ch == '\'' {
+            let quote = ch;
+            let start = i;
+            i += 1;
+
+            // Find closing quote
+            while i < chars.len() && chars[i] != quote {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                i += 1;
+            }
+
+            if i < chars.len() {
+                i += 1; // Include closing quote
+            }
+
+            let literal: String = chars[start..i].iter().collect();
+            result.push_str(&html_span(CSS_CLASS_STRING, &literal));
+            continue;
+        }
+        // Variables
+        else if ch == '$' {
+            let start = i;
+            i += 1;
+
+            // Collect variable name
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '{' || chars[i] == '}') {
+                i += 1;
+            }
+
+            let variable: String = chars[start..i].iter().collect();
+            result.push_str(&html_span(CSS_CLASS_VARIABLE, &variable));
+            continue;
+        }
+        // Keywords
+        else if ch.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+
+            let word: String = chars[start..i].iter().collect();
+            if is_shell_keyword(&word) {
+                result.push_str(&html_span(CSS_CLASS_KEYWORD, &word));
+            } else {
+                result.push_str(&html_escape(&word));
+            }
+            continue;
+        }
+        // Operators
+        else if "=<>!&|".contains(ch) {
+            result.push_str(&html_span(CSS_CLASS_OPERATOR, &ch.to_string()));
+        }
+        // Normal characters
+        else {
+            result.push_str(&html_escape(&ch.to_string()));
+        }
+
+        i += 1;
+    }
+
+    result
+}
+
 /// Shell keyword detection using pure Rust
 fn is_shell_keyword(word: &str) -> bool {
     matches!(word, 
@@ -240,6 +898,41 @@ pub extern "C" fn rust_get_version() -> *const CChar {
     b"runepkg-highlight 1.0.0 (clean-slate)\0".as_ptr() as *const CChar
 }
 
+/// Export the active theme's token colors as a 16-entry console palette and install it on the
+/// Linux virtual console open on `fd`, the way `vtcol` writes a palette via `PIO_CMAP`
+///
+/// # Safety
+/// This function is unsafe because it performs a raw `ioctl` on a caller-supplied file descriptor
+#[no_mangle]
+pub unsafe extern "C" fn rust_apply_console_palette(fd: CInt) -> CInt {
+    let mut kb_type: u8 = 0;
+    if ioctl(fd, KDGKBTYPE, &mut kb_type as *mut u8) != 0 {
+        return -1; // Not a console
+    }
+
+    let theme = current_theme();
+    let mut palette = [0u8; 48];
+    for (i, (r, g, b)) in VGA16.iter().enumerate() {
+        palette[i * 3] = *r;
+        palette[i * 3 + 1] = *g;
+        palette[i * 3 + 2] = *b;
+    }
+
+    for param in [&theme.comment, &theme.string, &theme.keyword, &theme.variable, &theme.operator] {
+        let index = ansi_palette_index(param);
+        let (r, g, b) = VGA16[index];
+        palette[index * 3] = r;
+        palette[index * 3 + 1] = g;
+        palette[index * 3 + 2] = b;
+    }
+
+    if ioctl(fd, PIO_CMAP, palette.as_mut_ptr()) != 0 {
+        return -2; // Palette install failed
+    }
+
+    0
+}
+
 /// Execute script from memory - minimal placeholder
 #[no_mangle]
 pub unsafe extern "C" fn rust_execute_script_from_memory(
@@ -250,24 +943,119 @@ pub unsafe extern "C" fn rust_execute_script_from_memory(
     0 // Not implemented in clean slate version
 }
 
-/// Parse shebang line - minimal placeholder
+/// Parse the leading `#!` line, resolving `#!/usr/bin/env bash` to the interpreter after `env`
+fn parse_shebang_internal(script: &str) -> Option<String> {
+    let first_line = script.lines().next()?;
+    if !first_line.starts_with("#!") {
+        return None;
+    }
+
+    let content = first_line[2..].trim();
+    if content.is_empty() {
+        return None;
+    }
+
+    let mut parts = content.split_whitespace();
+    let first = parts.next()?;
+    let basename = first.rsplit('/').next().unwrap_or(first);
+
+    if basename == "env" {
+        let rest: Vec<&str> = parts.collect();
+        if rest.is_empty() {
+            return None;
+        }
+        return Some(rest.join(" "));
+    }
+
+    Some(content.to_string())
+}
+
+/// Derive a language name (`sh`, `bash`, `zsh`, `python`, `perl`, `ruby`, `node`, …) from the
+/// shebang basename, falling back to lightweight content heuristics when there is no shebang
+fn detect_script_type_name(script: &str) -> String {
+    if let Some(shebang) = parse_shebang_internal(script) {
+        let first_tok = shebang.split_whitespace().next().unwrap_or("");
+        let basename = first_tok.rsplit('/').next().unwrap_or(first_tok).to_lowercase();
+
+        if basename.starts_with("bash") {
+            return "bash".to_string();
+        } else if basename.starts_with("zsh") {
+            return "zsh".to_string();
+        } else if basename.starts_with("sh") {
+            return "sh".to_string();
+        } else if basename.starts_with("python") {
+            return "python".to_string();
+        } else if basename.starts_with("perl") {
+            return "perl".to_string();
+        } else if basename.starts_with("ruby") {
+            return "ruby".to_string();
+        } else if basename.starts_with("node") {
+            return "node".to_string();
+        }
+        return basename;
+    }
+
+    // No shebang - fall back to lightweight content heuristics
+    if script.contains("def ") || script.contains("import ") {
+        "python".to_string()
+    } else if script.contains("use strict") {
+        "perl".to_string()
+    } else if script.contains("function ") || script.contains("$(") || script.contains("if [") {
+        "sh".to_string()
+    } else {
+        "unknown".to_string()
+    }
+}
+
+/// Parse shebang line, returning the resolved interpreter and its arguments
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
 #[no_mangle]
 pub unsafe extern "C" fn rust_parse_shebang(
-    _script_content: *const CChar,
-    _script_len: CInt,
+    script_content: *const CChar,
+    script_len: CInt,
 ) -> *mut CChar {
-    // Clean slate: minimal implementation
-    ptr::null_mut()
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    match parse_shebang_internal(script_str) {
+        Some(shebang) => match CString::new(shebang) {
+            Ok(c_string) => c_string.into_raw(),
+            Err(_) => ptr::null_mut(),
+        },
+        None => ptr::null_mut(),
+    }
 }
 
-/// Detect script type - minimal placeholder
+/// Detect the script's language, so callers can pick an appropriate keyword set instead of
+/// always using the shell keyword list in `is_shell_keyword`
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
 #[no_mangle]
 pub unsafe extern "C" fn rust_detect_script_type(
-    _script_content: *const CChar,
-    _script_len: CInt,
+    script_content: *const CChar,
+    script_len: CInt,
 ) -> *mut CChar {
-    // Clean slate: return "shell" as default
-    match CString::new("shell") {
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    match CString::new(detect_script_type_name(script_str)) {
         Ok(c_string) => c_string.into_raw(),
         Err(_) => ptr::null_mut(),
     }
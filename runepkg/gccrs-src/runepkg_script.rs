@@ -9,7 +9,8 @@
 
 extern crate alloc;
 
-use alloc::string::String;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::ptr;
 use core::slice;
 use core::str;
@@ -19,6 +20,25 @@ pub type c_char = i8;
 pub type c_int = i32;
 pub type size_t = usize;
 
+/// Upper bound on any caller-supplied length (`script_len`, `buffer_size`, `filename_len`, ...).
+///
+/// This module is `#![no_std]` and built with `panic = abort`, so there is no unwind for a
+/// `std::panic::catch_unwind`-style guard to intercept at the FFI boundary - a panic here
+/// aborts the process outright. The real protection is rejecting lengths that could never
+/// describe a real script or buffer *before* they reach `slice::from_raw_parts` or buffer
+/// size arithmetic, which is what every `rust_*` entry point below does via [`checked_len`].
+const MAX_INPUT_LEN: c_int = 64 * 1024 * 1024; // 64 MiB
+
+/// Validate a caller-supplied length, rejecting non-positive and implausibly large values
+/// before it's ever used to build a slice or size a buffer
+fn checked_len(len: c_int) -> Option<usize> {
+    if len <= 0 || len > MAX_INPUT_LEN {
+        None
+    } else {
+        Some(len as usize)
+    }
+}
+
 /// Script type detection - pure Rust enums
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -28,6 +48,11 @@ pub enum ScriptType {
     Perl = 2,
     Ruby = 3,
     Unknown = 4,
+    // Appended after Unknown so existing C callers keep their numeric mapping
+    JavaScript = 5,
+    Lua = 6,
+    PowerShell = 7,
+    Awk = 8,
 }
 
 /// Detect script type from content - completely self-contained
@@ -39,11 +64,15 @@ pub unsafe extern "C" fn rust_detect_script_type(
     script_content: *const c_char,
     script_len: c_int,
 ) -> ScriptType {
-    if script_content.is_null() || script_len <= 0 {
+    if script_content.is_null() {
         return ScriptType::Unknown;
     }
+    let len = match checked_len(script_len) {
+        Some(len) => len,
+        None => return ScriptType::Unknown,
+    };
 
-    let script_slice = slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_slice = slice::from_raw_parts(script_content as *const u8, len);
     let script_str = match str::from_utf8(script_slice) {
         Ok(s) => s,
         Err(_) => return ScriptType::Unknown,
@@ -68,18 +97,23 @@ fn detect_script_type_internal(script: &str) -> ScriptType {
         return ScriptType::Unknown;
     }
     
-    // Check shebang using pure string operations
+    // Check shebang, resolving an `env` wrapper and normalizing version suffixes first
+    // (`#!/usr/bin/env python3.11` should match the same as `#!/usr/bin/python`)
     if first_line.starts_with("#!") {
         let shebang = &first_line[2..];
-        
-        if shebang.contains("bash") || shebang.contains("sh") || shebang.contains("/bin/sh") {
-            return ScriptType::Shell;
-        } else if shebang.contains("python") {
-            return ScriptType::Python;
-        } else if shebang.contains("perl") {
-            return ScriptType::Perl;
-        } else if shebang.contains("ruby") {
-            return ScriptType::Ruby;
+
+        if let Some(interpreter) = resolve_shebang_interpreter(shebang) {
+            match interpreter {
+                "bash" | "sh" | "dash" | "zsh" | "ksh" => return ScriptType::Shell,
+                "python" => return ScriptType::Python,
+                "perl" => return ScriptType::Perl,
+                "ruby" => return ScriptType::Ruby,
+                "node" | "nodejs" => return ScriptType::JavaScript,
+                "lua" => return ScriptType::Lua,
+                "pwsh" | "powershell" => return ScriptType::PowerShell,
+                "awk" | "gawk" | "mawk" => return ScriptType::Awk,
+                _ => {}
+            }
         }
     }
     
@@ -97,6 +131,76 @@ fn detect_script_type_internal(script: &str) -> ScriptType {
     ScriptType::Unknown
 }
 
+/// The final path segment of `path`, i.e. the interpreter's basename
+fn basename(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Strip a trailing version suffix made of digits and dots, e.g. normalizing
+/// `python3.11` and `ruby2.7` down to `python`/`ruby` before matching
+fn strip_version_suffix(name: &str) -> &str {
+    let end = name
+        .rfind(|c: char| !c.is_ascii_digit() && c != '.')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    &name[..end]
+}
+
+/// Resolve the effective interpreter name from a shebang line's content (the part after
+/// `#!`), unwrapping an `env` wrapper (`#!/usr/bin/env python3` -> `python3` -> `python`)
+/// and normalizing away trailing version digits
+fn resolve_shebang_interpreter(shebang: &str) -> Option<&str> {
+    let mut parts = shebang.split_whitespace();
+    let first = basename(parts.next()?);
+
+    let interpreter = if first == "env" { parts.next()? } else { first };
+
+    Some(strip_version_suffix(interpreter))
+}
+
+/// Detect script type purely from a filename's extension, useful when a script has no
+/// shebang at all
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_detect_script_type_by_name(
+    filename: *const c_char,
+    filename_len: c_int,
+) -> ScriptType {
+    if filename.is_null() {
+        return ScriptType::Unknown;
+    }
+    let len = match checked_len(filename_len) {
+        Some(len) => len,
+        None => return ScriptType::Unknown,
+    };
+
+    let name_slice = slice::from_raw_parts(filename as *const u8, len);
+    let name_str = match str::from_utf8(name_slice) {
+        Ok(s) => s,
+        Err(_) => return ScriptType::Unknown,
+    };
+
+    detect_script_type_by_extension(name_str)
+}
+
+/// Internal extension-to-type mapping, mirroring how tooling keys off file extensions
+/// when no content marker (shebang) exists
+fn detect_script_type_by_extension(filename: &str) -> ScriptType {
+    match filename.rsplit('.').next().unwrap_or("") {
+        "sh" => ScriptType::Shell,
+        "py" => ScriptType::Python,
+        "pl" => ScriptType::Perl,
+        "rb" => ScriptType::Ruby,
+        "js" => ScriptType::JavaScript,
+        "lua" => ScriptType::Lua,
+        "ps1" => ScriptType::PowerShell,
+        "awk" => ScriptType::Awk,
+        _ => ScriptType::Unknown,
+    }
+}
+
 /// Extract metadata from script comments - pure Rust implementation
 /// 
 /// # Safety
@@ -108,11 +212,19 @@ pub unsafe extern "C" fn rust_extract_script_metadata(
     metadata_buffer: *mut c_char,
     buffer_size: c_int,
 ) -> c_int {
-    if script_content.is_null() || metadata_buffer.is_null() || script_len <= 0 || buffer_size <= 0 {
+    if script_content.is_null() || metadata_buffer.is_null() {
         return 0;
     }
+    let script_len = match checked_len(script_len) {
+        Some(len) => len,
+        None => return 0,
+    };
+    let buffer_size = match checked_len(buffer_size) {
+        Some(len) => len,
+        None => return 0,
+    };
 
-    let script_slice = slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_slice = slice::from_raw_parts(script_content as *const u8, script_len);
     let script_str = match str::from_utf8(script_slice) {
         Ok(s) => s,
         Err(_) => return 0,
@@ -120,49 +232,109 @@ pub unsafe extern "C" fn rust_extract_script_metadata(
 
     let metadata = extract_metadata_internal(script_str);
     let metadata_bytes = metadata.as_bytes();
-    let copy_len = core::cmp::min(metadata_bytes.len(), (buffer_size - 1) as usize);
-    
+    let required_len = metadata_bytes.len();
+
+    if required_len > buffer_size - 1 {
+        // Buffer too small: report the negative required length (including the NUL
+        // terminator) so the caller can retry with a buffer of at least that size,
+        // instead of silently truncating
+        return -(required_len as c_int) - 1;
+    }
+
     // Copy metadata to buffer using core functionality
-    let buffer_slice = slice::from_raw_parts_mut(metadata_buffer as *mut u8, buffer_size as usize);
-    buffer_slice[..copy_len].copy_from_slice(&metadata_bytes[..copy_len]);
-    buffer_slice[copy_len] = 0; // Null terminator
-    
-    copy_len as c_int
+    let buffer_slice = slice::from_raw_parts_mut(metadata_buffer as *mut u8, buffer_size);
+    buffer_slice[..required_len].copy_from_slice(metadata_bytes);
+    buffer_slice[required_len] = 0; // Null terminator
+
+    required_len as c_int
 }
 
-/// Internal metadata extraction using pure Rust string operations
-fn extract_metadata_internal(script: &str) -> String {
-    let mut metadata = String::new();
-    
+/// Number of leading lines inspected for metadata comments
+const METADATA_SCAN_LINES: usize = 20;
+
+/// Parse the comment lines within the first `METADATA_SCAN_LINES` lines (non-comment lines
+/// in between are skipped) into ordered `key: value` records, lowercasing keys
+/// and honoring RFC-822-style continuation: an indented comment line with no colon
+/// appends to the previous record's value
+fn parse_metadata_records(script: &str) -> Vec<(String, String)> {
+    let mut records: Vec<(String, String)> = Vec::new();
+
     for (line_num, line) in script.lines().enumerate() {
-        if line_num >= 20 { // Only check first 20 lines
+        if line_num >= METADATA_SCAN_LINES {
             break;
         }
-        
-        let trimmed = line.trim();
-        
-        // Look for comment metadata using pure string methods
-        if trimmed.starts_with('#') {
-            let comment = trimmed[1..].trim();
-            
-            // Common metadata patterns - case insensitive using pure Rust
-            let comment_lower = comment.to_lowercase();
-            if comment_lower.contains("author:") ||
-               comment_lower.contains("description:") ||
-               comment_lower.contains("version:") ||
-               comment_lower.contains("usage:") {
-                if !metadata.is_empty() {
-                    metadata.push('\n');
+
+        let trimmed = line.trim_start();
+        if !trimmed.starts_with('#') {
+            continue;
+        }
+
+        let body = &trimmed[1..];
+        // A single space after `#` is the baseline comment style (`# Description: foo`),
+        // not a fold marker; continuation requires *extra* indentation in the body itself
+        // (a tab, or 2+ leading spaces), e.g. `#   more text` or `#\tmore text`
+        let body_indented = body.starts_with('\t') || body.starts_with("  ");
+        let comment = body.trim();
+        if comment.is_empty() {
+            continue;
+        }
+
+        match comment.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                records.push((key.trim().to_lowercase(), value.trim().to_string()));
+            }
+            _ => {
+                // A continuation line has its comment body indented beyond the single
+                // baseline space, regardless of whether the `#` itself is indented;
+                // anything else with no colon is just a plain comment and is ignored
+                if body_indented {
+                    if let Some(last) = records.last_mut() {
+                        if !last.1.is_empty() {
+                            last.1.push('\n');
+                        }
+                        last.1.push_str(comment);
+                    }
                 }
-                metadata.push_str(comment);
             }
         }
     }
-    
-    if metadata.is_empty() {
-        metadata.push_str("No metadata found");
+
+    records
+}
+
+/// Percent-escape characters that would otherwise break the one-record-per-line
+/// `key=value` output: embedded newlines, carriage returns, and literal `%`
+fn percent_escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+    for ch in value.chars() {
+        match ch {
+            '\n' => escaped.push_str("%0A"),
+            '\r' => escaped.push_str("%0D"),
+            '%' => escaped.push_str("%25"),
+            _ => escaped.push(ch),
+        }
     }
-    
+    escaped
+}
+
+/// Internal metadata extraction: parses structured `key: value` comment records and
+/// renders them as deterministic, machine-readable `key=value` lines
+fn extract_metadata_internal(script: &str) -> String {
+    let records = parse_metadata_records(script);
+    if records.is_empty() {
+        return String::from("No metadata found");
+    }
+
+    let mut metadata = String::new();
+    for (key, value) in &records {
+        if !metadata.is_empty() {
+            metadata.push('\n');
+        }
+        metadata.push_str(key);
+        metadata.push('=');
+        metadata.push_str(&percent_escape_value(value));
+    }
+
     metadata
 }
 
@@ -176,11 +348,15 @@ pub unsafe extern "C" fn rust_validate_script_syntax(
     script_len: c_int,
     script_type: ScriptType,
 ) -> c_int {
-    if script_content.is_null() || script_len <= 0 {
+    if script_content.is_null() {
         return 0;
     }
+    let len = match checked_len(script_len) {
+        Some(len) => len,
+        None => return 0,
+    };
 
-    let script_slice = slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_slice = slice::from_raw_parts(script_content as *const u8, len);
     let script_str = match str::from_utf8(script_slice) {
         Ok(s) => s,
         Err(_) => return 0,
@@ -200,32 +376,173 @@ fn validate_syntax_internal(script: &str, script_type: ScriptType) -> bool {
         ScriptType::Python => validate_python_syntax(script),
         ScriptType::Perl => validate_perl_syntax(script),
         ScriptType::Ruby => validate_ruby_syntax(script),
-        ScriptType::Unknown => false,
+        ScriptType::Unknown
+        | ScriptType::JavaScript
+        | ScriptType::Lua
+        | ScriptType::PowerShell
+        | ScriptType::Awk => false,
     }
 }
 
-/// Basic shell syntax validation using pure Rust
+/// A single token produced by [`tokens`]: a brace, a whitespace-delimited word, or a newline.
+/// Only emitted while the lexer is in `Normal` state, so text inside strings/comments never
+/// surfaces here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    LBrace,
+    RBrace,
+    Word(&'a str),
+    Newline,
+}
+
+/// Lexer state for [`Tokens`]
+#[derive(Clone, Copy, PartialEq)]
+enum LexState {
+    Normal,
+    InSingle,
+    InDouble,
+    InLineComment,
+}
+
+/// Single-pass, string- and comment-aware tokenizer shared by all `validate_*_syntax` checks.
+/// Tracks quote/comment state so a brace or keyword inside a string literal or a trailing
+/// `#` comment is never mistaken for real script structure.
+struct Tokens<'a> {
+    src: &'a str,
+    iter: core::iter::Peekable<core::str::CharIndices<'a>>,
+    state: LexState,
+    unterminated_string: bool,
+}
+
+impl<'a> Tokens<'a> {
+    /// Whether a quoted string was still open when the input ended; callers should treat
+    /// this as an invalid script rather than trusting the brace/keyword counts seen so far
+    fn unterminated_string(&self) -> bool {
+        self.unterminated_string
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let (idx, ch) = match self.iter.peek().copied() {
+                Some(v) => v,
+                None => {
+                    if matches!(self.state, LexState::InSingle | LexState::InDouble) {
+                        self.unterminated_string = true;
+                    }
+                    return None;
+                }
+            };
+
+            match self.state {
+                LexState::InLineComment => {
+                    self.iter.next();
+                    if ch == '\n' {
+                        self.state = LexState::Normal;
+                        return Some(Token::Newline);
+                    }
+                }
+                LexState::InSingle | LexState::InDouble => {
+                    let closing = if self.state == LexState::InSingle { '\'' } else { '"' };
+                    self.iter.next();
+                    if ch == '\\' {
+                        self.iter.next(); // honor the escape, whatever it precedes
+                    } else if ch == closing {
+                        self.state = LexState::Normal;
+                    }
+                }
+                LexState::Normal => match ch {
+                    '\n' => {
+                        self.iter.next();
+                        return Some(Token::Newline);
+                    }
+                    c if c.is_whitespace() => {
+                        self.iter.next();
+                    }
+                    '\'' => {
+                        self.iter.next();
+                        self.state = LexState::InSingle;
+                    }
+                    '"' => {
+                        self.iter.next();
+                        self.state = LexState::InDouble;
+                    }
+                    '#' => {
+                        self.iter.next();
+                        self.state = LexState::InLineComment;
+                    }
+                    '{' => {
+                        self.iter.next();
+                        return Some(Token::LBrace);
+                    }
+                    '}' => {
+                        self.iter.next();
+                        return Some(Token::RBrace);
+                    }
+                    _ => {
+                        let start = idx;
+                        let mut end = idx;
+                        while let Some(&(i, c)) = self.iter.peek() {
+                            if c.is_whitespace() || matches!(c, '\'' | '"' | '#' | '{' | '}') {
+                                break;
+                            }
+                            end = i + c.len_utf8();
+                            self.iter.next();
+                        }
+                        return Some(Token::Word(&self.src[start..end]));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Build the shared token stream used by all `validate_*_syntax` checks
+fn tokens(script: &str) -> Tokens<'_> {
+    Tokens {
+        src: script,
+        iter: script.char_indices().peekable(),
+        state: LexState::Normal,
+        unterminated_string: false,
+    }
+}
+
+/// Basic shell syntax validation using the string/comment-aware token stream, so keywords
+/// and braces inside quoted strings or trailing comments aren't mistaken for real structure
 fn validate_shell_syntax(script: &str) -> bool {
     let mut if_count = 0;
     let mut fi_count = 0;
     let mut for_count = 0;
     let mut done_count = 0;
-    
-    for line in script.lines() {
-        let trimmed = line.trim();
-        
-        // Basic bracket matching using pure string operations
-        if trimmed.starts_with("if ") {
-            if_count += 1;
-        } else if trimmed == "fi" {
-            fi_count += 1;
-        } else if trimmed.starts_with("for ") {
-            for_count += 1;
-        } else if trimmed == "done" {
-            done_count += 1;
+    let mut at_line_start = true;
+
+    let mut lexer = tokens(script);
+    for tok in &mut lexer {
+        match tok {
+            Token::Newline => at_line_start = true,
+            Token::Word(word) => {
+                if at_line_start {
+                    match word {
+                        "if" => if_count += 1,
+                        "fi" => fi_count += 1,
+                        "for" => for_count += 1,
+                        "done" => done_count += 1,
+                        _ => {}
+                    }
+                }
+                at_line_start = false;
+            }
+            _ => at_line_start = false,
         }
     }
-    
+
+    if lexer.unterminated_string() {
+        return false;
+    }
+
     // Basic structure validation
     if_count == fi_count && for_count == done_count
 }
@@ -258,46 +575,353 @@ fn validate_python_syntax(script: &str) -> bool {
     true // Basic validation passed
 }
 
-/// Basic Perl syntax validation using pure Rust
+/// Basic Perl syntax validation using the string/comment-aware token stream, so braces
+/// that only appear inside a quoted string or a trailing `#` comment aren't counted
 fn validate_perl_syntax(script: &str) -> bool {
     let mut brace_count = 0;
-    
-    for line in script.lines() {
-        for ch in line.chars() {
-            match ch {
-                '{' => brace_count += 1,
-                '}' => brace_count -= 1,
-                _ => {}
-            }
+
+    let mut lexer = tokens(script);
+    for tok in &mut lexer {
+        match tok {
+            Token::LBrace => brace_count += 1,
+            Token::RBrace => brace_count -= 1,
+            _ => {}
         }
     }
-    
+
+    if lexer.unterminated_string() {
+        return false;
+    }
+
     brace_count == 0
 }
 
-/// Basic Ruby syntax validation using pure Rust
+/// Basic Ruby syntax validation using the string/comment-aware token stream, requiring
+/// whole-word matches so identifiers like `endpoint` aren't mistaken for the `end` keyword
 fn validate_ruby_syntax(script: &str) -> bool {
     let mut end_count = 0;
     let mut begin_count = 0;
-    
+    let mut at_line_start = true;
+
+    let mut lexer = tokens(script);
+    for tok in &mut lexer {
+        match tok {
+            Token::Newline => at_line_start = true,
+            Token::Word(word) => {
+                if at_line_start {
+                    match word {
+                        "def" | "class" | "module" | "if" | "unless" | "while" | "for" | "begin" => {
+                            begin_count += 1;
+                        }
+                        "end" => end_count += 1,
+                        _ => {}
+                    }
+                }
+                at_line_start = false;
+            }
+            _ => at_line_start = false,
+        }
+    }
+
+    if lexer.unterminated_string() {
+        return false;
+    }
+
+    begin_count == end_count
+}
+
+/// Maximum number of distinct dependencies returned by `rust_extract_script_dependencies`,
+/// bounding output so it can't overflow the caller's buffer
+const MAX_DEPENDENCIES: usize = 64;
+
+/// Shell builtins and keywords that are never themselves a runtime dependency
+const SHELL_BUILTINS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "return", "break", "continue", "cd", "echo", "export", "unset", "set",
+    "shift", "exit", "local", "readonly", "trap", "test", "let", "eval", "exec", "source",
+    "alias", "read", "printf", "true", "false",
+];
+
+/// Push `name` onto `deps` if it isn't already present and the cap hasn't been hit
+fn push_dependency(deps: &mut Vec<String>, name: &str) {
+    if name.is_empty() || deps.len() >= MAX_DEPENDENCIES {
+        return;
+    }
+    if !deps.iter().any(|existing| existing == name) {
+        deps.push(String::from(name));
+    }
+}
+
+/// Whether `word` is a shell builtin/keyword rather than an invoked external command
+fn is_shell_builtin(word: &str) -> bool {
+    SHELL_BUILTINS.contains(&word)
+}
+
+/// Whether `word` is a variable assignment (`FOO=bar`) rather than a command name
+fn is_assignment(word: &str) -> bool {
+    match word.find('=') {
+        Some(pos) if pos > 0 => {
+            let name = &word[..pos];
+            name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Scan a shell script for invoked command names: the first bare word of each line that
+/// isn't a builtin/keyword or a variable assignment. Reuses the shared tokenizer so words
+/// inside quoted strings or comments are never captured.
+fn extract_shell_dependencies(script: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+    let mut at_line_start = true;
+
+    for tok in tokens(script) {
+        match tok {
+            Token::Newline => at_line_start = true,
+            Token::Word(word) => {
+                if at_line_start && !is_shell_builtin(word) && !is_assignment(word) {
+                    push_dependency(&mut deps, word);
+                }
+                at_line_start = false;
+            }
+            _ => at_line_start = false,
+        }
+    }
+
+    deps
+}
+
+/// The first `.`/whitespace-delimited segment of a dotted module path
+/// (`os.path` -> `os`, `numpy as np` -> `numpy`)
+fn first_dotted_segment(s: &str) -> Option<&str> {
+    let trimmed = s.trim();
+    let segment = trimmed.split(['.', ' ']).next().unwrap_or("");
+    if segment.is_empty() {
+        None
+    } else {
+        Some(segment)
+    }
+}
+
+/// Parse `import X` / `from X import ...` statements, keeping only the first dotted
+/// segment of the module path
+fn extract_python_dependencies(script: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+
     for line in script.lines() {
         let trimmed = line.trim();
-        
-        if trimmed.starts_with("def ") || 
-           trimmed.starts_with("class ") ||
-           trimmed.starts_with("module ") ||
-           trimmed.starts_with("if ") ||
-           trimmed.starts_with("unless ") ||
-           trimmed.starts_with("while ") ||
-           trimmed.starts_with("for ") ||
-           trimmed.starts_with("begin") {
-            begin_count += 1;
-        } else if trimmed == "end" {
-            end_count += 1;
+        if let Some(rest) = trimmed.strip_prefix("import ") {
+            for part in rest.split(',') {
+                if let Some(name) = first_dotted_segment(part) {
+                    push_dependency(&mut deps, name);
+                }
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("from ") {
+            if let Some((module, _)) = rest.split_once(" import") {
+                if let Some(name) = first_dotted_segment(module) {
+                    push_dependency(&mut deps, name);
+                }
+            }
+        }
+    }
+
+    deps
+}
+
+/// Pull the argument out of a Ruby `require`/`require_relative` or Perl `use` statement,
+/// unquoting it and keeping only the first word (so `use POSIX qw(...)` yields `POSIX`)
+fn extract_require_argument(rest: &str) -> Option<&str> {
+    let rest = rest.split(';').next().unwrap_or(rest).trim();
+
+    let unquoted = rest
+        .strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .or_else(|| rest.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+        .unwrap_or(rest);
+
+    let name = unquoted.split_whitespace().next().unwrap_or(unquoted);
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Parse `require`/`require_relative`/`use` statement arguments (Ruby and Perl share this
+/// shape closely enough to reuse the same scan)
+fn extract_require_style_dependencies(script: &str) -> Vec<String> {
+    let mut deps = Vec::new();
+
+    for line in script.lines() {
+        let trimmed = line.trim();
+        let rest = trimmed
+            .strip_prefix("require_relative ")
+            .or_else(|| trimmed.strip_prefix("require "))
+            .or_else(|| trimmed.strip_prefix("use "));
+
+        if let Some(rest) = rest {
+            if let Some(name) = extract_require_argument(rest) {
+                push_dependency(&mut deps, name);
+            }
+        }
+    }
+
+    deps
+}
+
+/// Internal dependency extraction, dispatching on script type
+fn extract_dependencies_internal(script: &str, script_type: ScriptType) -> Vec<String> {
+    match script_type {
+        ScriptType::Shell => extract_shell_dependencies(script),
+        ScriptType::Python => extract_python_dependencies(script),
+        ScriptType::Ruby | ScriptType::Perl => extract_require_style_dependencies(script),
+        ScriptType::Unknown
+        | ScriptType::JavaScript
+        | ScriptType::Lua
+        | ScriptType::PowerShell
+        | ScriptType::Awk => Vec::new(),
+    }
+}
+
+/// Enumerate a script's implicit runtime dependencies (imported modules / invoked
+/// commands), dispatching on `script_type`. Output is deduplicated, newline-separated,
+/// and capped at `MAX_DEPENDENCIES` entries so it can't overflow the caller's buffer.
+///
+/// Returns the number of bytes written (excluding the NUL terminator), or, if `out_buf`
+/// is too small, the negative of the required buffer size so the caller can retry.
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_extract_script_dependencies(
+    script_content: *const c_char,
+    script_len: c_int,
+    script_type: ScriptType,
+    out_buf: *mut c_char,
+    buf_size: c_int,
+) -> c_int {
+    if script_content.is_null() || out_buf.is_null() {
+        return 0;
+    }
+    let script_len = match checked_len(script_len) {
+        Some(len) => len,
+        None => return 0,
+    };
+    let buf_size = match checked_len(buf_size) {
+        Some(len) => len,
+        None => return 0,
+    };
+
+    let script_slice = slice::from_raw_parts(script_content as *const u8, script_len);
+    let script_str = match str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+
+    let deps = extract_dependencies_internal(script_str, script_type);
+    let joined = deps.join("\n");
+    let joined_bytes = joined.as_bytes();
+    let required_len = joined_bytes.len();
+
+    if required_len > buf_size - 1 {
+        return -(required_len as c_int) - 1;
+    }
+
+    let buffer_slice = slice::from_raw_parts_mut(out_buf as *mut u8, buf_size);
+    buffer_slice[..required_len].copy_from_slice(joined_bytes);
+    buffer_slice[required_len] = 0;
+
+    required_len as c_int
+}
+
+/// Convergence-style mutation fuzz harness (in the spirit of the old Rust fuzzer's
+/// `tm_converge` mode): repeatedly mutate a seed script and assert that every analysis
+/// entry point returns *some* value without panicking across all the mutants. Gated behind
+/// the `fuzz` feature so the zero-dependency default build is unaffected.
+#[cfg(feature = "fuzz")]
+pub mod fuzz {
+    use super::*;
+
+    /// Minimal xorshift32 PRNG so the harness stays dependency-free
+    struct Rng(u32);
+
+    impl Rng {
+        fn next_u32(&mut self) -> u32 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            self.0 = x;
+            x
+        }
+
+        fn next_range(&mut self, bound: usize) -> usize {
+            if bound == 0 {
+                0
+            } else {
+                (self.next_u32() as usize) % bound
+            }
+        }
+    }
+
+    /// Apply one random line-level mutation: delete a line, duplicate a brace onto a line,
+    /// flip the shebang, or duplicate a line
+    fn mutate(script: &str, rng: &mut Rng) -> String {
+        let mut lines: Vec<String> = script.lines().map(String::from).collect();
+        if lines.is_empty() {
+            return String::from(script);
+        }
+
+        match rng.next_range(4) {
+            0 => {
+                let idx = rng.next_range(lines.len());
+                lines.remove(idx);
+            }
+            1 => {
+                let idx = rng.next_range(lines.len());
+                lines[idx].push('{');
+            }
+            2 => {
+                if lines[0].starts_with("#!") {
+                    lines[0] = String::from("#!/flipped/interpreter");
+                }
+            }
+            _ => {
+                let idx = rng.next_range(lines.len());
+                let dup = lines[idx].clone();
+                lines.insert(idx, dup);
+            }
+        }
+
+        let mut out = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(line);
+        }
+        out
+    }
+
+    /// Run `rounds` generations of mutation starting from `seed`, feeding every mutant
+    /// through the detection/metadata/validation entry points. Completing without a panic
+    /// (process abort, since this is `no_std`) is the pass condition.
+    pub fn converge(seed: &str, rounds: usize, seed_rng: u32) {
+        let mut rng = Rng(if seed_rng == 0 { 1 } else { seed_rng }); // xorshift32 can't escape an all-zero state
+        let mut current = String::from(seed);
+
+        for _ in 0..rounds {
+            current = mutate(&current, &mut rng);
+
+            let _ = detect_script_type_internal(&current);
+            let _ = extract_metadata_internal(&current);
+            let _ = validate_shell_syntax(&current);
+            let _ = validate_python_syntax(&current);
+            let _ = validate_perl_syntax(&current);
+            let _ = validate_ruby_syntax(&current);
         }
     }
-    
-    begin_count == end_count
 }
 
 // Clean slate - no test module to avoid external dependencies
@@ -2,9 +2,83 @@
 /// Provides secure script execution with highlighting support
 use libc::{c_char, c_int, size_t};
 use std::ffi::{CStr, CString};
-use std::process::{Command, Stdio};
-use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::io::{Read, Write};
 use std::ptr;
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
+
+/// Result of `rust_execute_script_capture` and `rust_execute_script_with_timeout`: exit status
+/// plus captured stdout/stderr
+///
+/// Freed with `rust_free_exec_result`.
+#[repr(C)]
+pub struct ExecResult {
+    pub exit_code: c_int,
+    // 0 = exited normally (exit_code valid) or never spawned (exit_code holds EXIT_NUL_IN_ARGV or
+    // EXIT_INTERPRETER_NOT_FOUND); 1 = terminated by signal (exit_code holds the signal number,
+    // or EXIT_TIMEOUT when killed by `rust_execute_script_with_timeout`)
+    pub signaled: c_int,
+    pub stdout: *mut c_char,
+    pub stdout_len: size_t,
+    pub stderr: *mut c_char,
+    pub stderr_len: size_t,
+}
+
+/// malloc a NUL-terminated buffer holding `data`, returning the pointer and the data length
+/// (excluding the terminator) so embedded NUL bytes in captured output don't get mistaken for EOF
+unsafe fn alloc_nul_terminated(data: &[u8]) -> (*mut c_char, size_t) {
+    let len = data.len();
+    let buf = libc::malloc(len + 1) as *mut u8;
+    if buf.is_null() {
+        return (ptr::null_mut(), 0);
+    }
+    ptr::copy_nonoverlapping(data.as_ptr(), buf, len);
+    *buf.add(len) = 0;
+    (buf as *mut c_char, len)
+}
+
+/// Build a malloc'd `ExecResult` from a completed child's output
+unsafe fn build_exec_result(output: &Output) -> *mut ExecResult {
+    let (exit_code, signaled) = match output.status.code() {
+        Some(code) => (code, 0),
+        None => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::ExitStatusExt;
+                (output.status.signal().unwrap_or(-1), 1)
+            }
+            #[cfg(not(unix))]
+            {
+                (-1, 1)
+            }
+        }
+    };
+
+    let (stdout, stdout_len) = alloc_nul_terminated(&output.stdout);
+    let (stderr, stderr_len) = alloc_nul_terminated(&output.stderr);
+
+    let result = libc::malloc(std::mem::size_of::<ExecResult>()) as *mut ExecResult;
+    if result.is_null() {
+        libc::free(stdout as *mut libc::c_void);
+        libc::free(stderr as *mut libc::c_void);
+        return ptr::null_mut();
+    }
+
+    ptr::write(
+        result,
+        ExecResult {
+            exit_code,
+            signaled,
+            stdout,
+            stdout_len,
+            stderr,
+            stderr_len,
+        },
+    );
+
+    result
+}
 
 /// Execute a shell script from memory with optional highlighting
 /// Based on execute_pkginfo_script from upkg_exec.c
@@ -43,11 +117,35 @@ pub unsafe extern "C" fn rust_execute_script_from_memory(
         }
     }
 
-    // Extract shebang interpreter (based on parse_shebang)
-    let interpreter = extract_shebang_interpreter(script_str).unwrap_or("/bin/sh".to_string());
-    
+    // Extract the shebang interpreter and its arguments (based on parse_shebang)
+    let (interpreter, interpreter_args) = resolve_shebang_command(script_str);
+
     // Execute the script
-    execute_script_internal(script_str, &interpreter)
+    execute_script_internal(script_str, &interpreter, &interpreter_args)
+}
+
+/// Default cap on shebang arguments passed through to the interpreter
+const DEFAULT_MAX_SHEBANG_ARGS: usize = 16;
+
+/// Exit code returned when the interpreter path or one of its arguments contains an interior NUL
+const EXIT_NUL_IN_ARGV: c_int = -2;
+/// Exit code returned when the interpreter could not be found (ENOENT), as opposed to other spawn failures
+const EXIT_INTERPRETER_NOT_FOUND: c_int = -3;
+
+/// Resolve the interpreter and its arguments from a script's shebang line (based on parse_shebang),
+/// defaulting to `/bin/sh` with no arguments when there is none
+fn resolve_shebang_command(script: &str) -> (String, Vec<String>) {
+    let mut args = extract_shebang_args(script, DEFAULT_MAX_SHEBANG_ARGS);
+    if args.is_empty() {
+        return ("/bin/sh".to_string(), Vec::new());
+    }
+    let interpreter = args.remove(0);
+    (interpreter, args)
+}
+
+/// Whether a string contains a NUL byte, which `Command::spawn` rejects with a late, confusing error
+fn contains_interior_nul(s: &str) -> bool {
+    s.as_bytes().contains(&0)
 }
 
 /// Extract interpreter from shebang line
@@ -75,19 +173,25 @@ fn extract_shebang_interpreter(script: &str) -> Option<String> {
 
 /// Internal script execution
 /// Based on the execution logic from upkg_exec.c
-fn execute_script_internal(script_content: &str, interpreter: &str) -> c_int {
+fn execute_script_internal(script_content: &str, interpreter: &str, args: &[String]) -> c_int {
+    if contains_interior_nul(interpreter) || args.iter().any(|arg| contains_interior_nul(arg)) {
+        return EXIT_NUL_IN_ARGV;
+    }
+
     // Check if interpreter is executable (mimicking access check)
-    let mut cmd = Command::new(interpreter)
+    let cmd = Command::new(interpreter)
+        .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn();
-        
+
     let mut child = match cmd {
         Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return EXIT_INTERPRETER_NOT_FOUND,
         Err(_) => return -1,
     };
-    
+
     // Write script to stdin (mimicking the pipe write in original)
     if let Some(mut stdin) = child.stdin.take() {
         if stdin.write_all(script_content.as_bytes()).is_err() {
@@ -108,6 +212,268 @@ fn execute_script_internal(script_content: &str, interpreter: &str) -> c_int {
     }
 }
 
+/// Execute a shell script from memory, capturing stdout/stderr and the exit status instead of
+/// discarding them, so callers can surface the actual error text from a failed install script
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_execute_script_capture(
+    script_content: *const c_char,
+    script_len: c_int,
+) -> *mut ExecResult {
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let (interpreter, interpreter_args) = resolve_shebang_command(script_str);
+    execute_script_capture_internal(script_str, &interpreter, &interpreter_args)
+}
+
+/// Build a malloc'd `ExecResult` carrying a pre-spawn failure sentinel (`EXIT_NUL_IN_ARGV`,
+/// `EXIT_INTERPRETER_NOT_FOUND`) with no captured output, for failures the child never reached
+unsafe fn synthetic_exec_result(exit_code: c_int) -> *mut ExecResult {
+    let result = libc::malloc(std::mem::size_of::<ExecResult>()) as *mut ExecResult;
+    if result.is_null() {
+        return ptr::null_mut();
+    }
+
+    ptr::write(
+        result,
+        ExecResult {
+            exit_code,
+            signaled: 0,
+            stdout: ptr::null_mut(),
+            stdout_len: 0,
+            stderr: ptr::null_mut(),
+            stderr_len: 0,
+        },
+    );
+
+    result
+}
+
+/// Internal capturing execution - mirrors `execute_script_internal` but keeps the output
+fn execute_script_capture_internal(script_content: &str, interpreter: &str, args: &[String]) -> *mut ExecResult {
+    if contains_interior_nul(interpreter) || args.iter().any(|arg| contains_interior_nul(arg)) {
+        return unsafe { synthetic_exec_result(EXIT_NUL_IN_ARGV) };
+    }
+
+    let child = Command::new(interpreter)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return unsafe { synthetic_exec_result(EXIT_INTERPRETER_NOT_FOUND) };
+        }
+        Err(_) => return ptr::null_mut(),
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if stdin.write_all(script_content.as_bytes()).is_err() {
+            return ptr::null_mut();
+        }
+    }
+
+    match child.wait_with_output() {
+        Ok(output) => unsafe { build_exec_result(&output) },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Exit code returned when the script did not finish within its timeout and had to be killed
+const EXIT_TIMEOUT: c_int = -4;
+/// How often to poll the child for completion while waiting out a timeout
+const POLL_INTERVAL_MS: u64 = 50;
+/// Grace period after SIGTERM before escalating to SIGKILL
+const SIGTERM_GRACE_MS: u64 = 2000;
+
+/// Execute a shell script from memory, capturing its output like `rust_execute_script_capture`,
+/// but killing the child (SIGTERM, then SIGKILL if it's still alive after a grace period) if it
+/// doesn't finish within `timeout_ms`, so a runaway install script can't hang the caller forever
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_execute_script_with_timeout(
+    script_content: *const c_char,
+    script_len: c_int,
+    timeout_ms: c_int,
+) -> *mut ExecResult {
+    if script_content.is_null() || script_len <= 0 || timeout_ms <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let (interpreter, interpreter_args) = resolve_shebang_command(script_str);
+    execute_script_with_timeout_internal(
+        script_str,
+        &interpreter,
+        &interpreter_args,
+        Duration::from_millis(timeout_ms as u64),
+    )
+}
+
+/// Internal timed execution - mirrors `execute_script_capture_internal`, polling for completion
+/// and escalating to SIGTERM/SIGKILL if the deadline passes before the child exits. Stdin is
+/// written and stdout/stderr are drained on background threads so a script that blocks on a full
+/// output pipe before reading its stdin can't stall the poll loop and defeat the timeout
+fn execute_script_with_timeout_internal(
+    script_content: &str,
+    interpreter: &str,
+    args: &[String],
+    timeout: Duration,
+) -> *mut ExecResult {
+    if contains_interior_nul(interpreter) || args.iter().any(|arg| contains_interior_nul(arg)) {
+        return unsafe { synthetic_exec_result(EXIT_NUL_IN_ARGV) };
+    }
+
+    let child = Command::new(interpreter)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return unsafe { synthetic_exec_result(EXIT_INTERPRETER_NOT_FOUND) };
+        }
+        Err(_) => return ptr::null_mut(),
+    };
+
+    // Write stdin and drain stdout/stderr on their own threads, concurrently with the poll loop
+    // below: a script that fills the ~64 KiB stdout/stderr pipe before reading its stdin would
+    // otherwise deadlock this thread inside `write_all`, so the timeout deadline never gets
+    // checked and the caller wedges anyway - exactly what the timeout exists to prevent
+    let mut stdin = child.stdin.take();
+    let script_owned = script_content.to_string();
+    let stdin_writer = thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(script_owned.as_bytes());
+        }
+    });
+
+    let mut stdout = child.stdout.take();
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let mut stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let timed_out = loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break false,
+            Ok(None) => {
+                if Instant::now() >= deadline {
+                    break true;
+                }
+                sleep(Duration::from_millis(POLL_INTERVAL_MS));
+            }
+            Err(_) => return ptr::null_mut(),
+        }
+    };
+
+    if timed_out {
+        kill_with_escalation(&mut child);
+    }
+
+    let _ = stdin_writer.join();
+    let stdout_buf = stdout_reader.join().unwrap_or_default();
+    let stderr_buf = stderr_reader.join().unwrap_or_default();
+
+    match child.wait() {
+        Ok(status) => unsafe {
+            let output = Output {
+                status,
+                stdout: stdout_buf,
+                stderr: stderr_buf,
+            };
+            let result = build_exec_result(&output);
+            if timed_out && !result.is_null() {
+                (*result).exit_code = EXIT_TIMEOUT;
+                (*result).signaled = 1;
+            }
+            result
+        },
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Send SIGTERM, then SIGKILL after `SIGTERM_GRACE_MS` if the child is still alive.
+/// Polls via `try_wait` (not a raw `waitpid`) so the child stays reapable by `wait` afterwards.
+fn kill_with_escalation(child: &mut std::process::Child) {
+    let pid = child.id() as libc::pid_t;
+    unsafe {
+        libc::kill(pid, libc::SIGTERM);
+    }
+
+    let grace_deadline = Instant::now() + Duration::from_millis(SIGTERM_GRACE_MS);
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) | Err(_) => return,
+            Ok(None) => {}
+        }
+        if Instant::now() >= grace_deadline {
+            break;
+        }
+        sleep(Duration::from_millis(POLL_INTERVAL_MS));
+    }
+
+    unsafe {
+        libc::kill(pid, libc::SIGKILL);
+    }
+}
+
+/// Free an `ExecResult` returned by `rust_execute_script_capture` or `rust_execute_script_with_timeout`
+///
+/// # Safety
+/// Assumes `result` was allocated by `rust_execute_script_capture` or `rust_execute_script_with_timeout`
+#[no_mangle]
+pub unsafe extern "C" fn rust_free_exec_result(result: *mut ExecResult) {
+    if result.is_null() {
+        return;
+    }
+
+    let owned = ptr::read(result);
+    if !owned.stdout.is_null() {
+        libc::free(owned.stdout as *mut libc::c_void);
+    }
+    if !owned.stderr.is_null() {
+        libc::free(owned.stderr as *mut libc::c_void);
+    }
+    libc::free(result as *mut libc::c_void);
+}
+
 /// Parse shebang and return interpreter path as C string
 /// Converts the parse_shebang functionality to return a C string
 /// 
@@ -323,4 +689,147 @@ mod tests {
         let interpreter = extract_shebang_interpreter(script);
         assert_eq!(interpreter, None);
     }
+
+    #[test]
+    fn test_execute_script_passes_shebang_args() {
+        // -e makes the shell exit immediately on the first failing command
+        let script = "#!/bin/sh -e\nfalse\necho should_not_run\n";
+        let args = extract_shebang_args(script, DEFAULT_MAX_SHEBANG_ARGS);
+        let code = execute_script_internal(script, &args[0], &args[1..]);
+        assert_ne!(code, 0);
+    }
+
+    #[test]
+    fn test_execute_script_rejects_interior_nul() {
+        let code = execute_script_internal("echo hi", "/bin/sh\0evil", &[]);
+        assert_eq!(code, EXIT_NUL_IN_ARGV);
+    }
+
+    #[test]
+    fn test_execute_script_reports_interpreter_not_found() {
+        let code = execute_script_internal("echo hi", "/no/such/interpreter", &[]);
+        assert_eq!(code, EXIT_INTERPRETER_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_execute_script_capture() {
+        let script = "echo hello; echo world 1>&2; exit 3";
+        let result = execute_script_capture_internal(script, "/bin/sh", &[]);
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, 3);
+            assert_eq!(owned.signaled, 0);
+
+            let stdout = CStr::from_ptr(owned.stdout).to_str().unwrap();
+            assert!(stdout.contains("hello"));
+
+            let stderr = CStr::from_ptr(owned.stderr).to_str().unwrap();
+            assert!(stderr.contains("world"));
+
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_capture_passes_shebang_args() {
+        // -e makes the shell exit immediately on the first failing command
+        let script = "#!/bin/sh -e\nfalse\necho should_not_run\n";
+        let args = extract_shebang_args(script, DEFAULT_MAX_SHEBANG_ARGS);
+        let result = execute_script_capture_internal(script, &args[0], &args[1..]);
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_ne!(owned.exit_code, 0);
+
+            let stdout = CStr::from_ptr(owned.stdout).to_str().unwrap();
+            assert!(!stdout.contains("should_not_run"));
+
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_capture_rejects_interior_nul() {
+        let result = execute_script_capture_internal("echo hi", "/bin/sh\0evil", &[]);
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, EXIT_NUL_IN_ARGV);
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_capture_reports_interpreter_not_found() {
+        let result = execute_script_capture_internal("echo hi", "/no/such/interpreter", &[]);
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, EXIT_INTERPRETER_NOT_FOUND);
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_with_timeout_kills_runaway_script() {
+        let script = "echo hi; sleep 5; echo should_not_print";
+        let result = execute_script_with_timeout_internal(script, "/bin/sh", &[], Duration::from_millis(200));
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, EXIT_TIMEOUT);
+            assert_eq!(owned.signaled, 1);
+
+            let stdout = CStr::from_ptr(owned.stdout).to_str().unwrap();
+            assert!(stdout.contains("hi"));
+            assert!(!stdout.contains("should_not_print"));
+
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_with_timeout_drains_large_output_without_deadlock() {
+        // Regression test for a deadlock: the script itself is padded past a single pipe's
+        // ~64 KiB buffer, and its first command also emits >64 KiB of stdout before the shell
+        // reads the rest of its own stdin. If the parent wrote the whole script to the child's
+        // stdin before draining stdout/stderr, both sides would block forever - the parent stuck
+        // in `write_all`, the child stuck writing stdout nobody is reading yet - and the timeout
+        // deadline below would never get checked.
+        let padding = "# pad\n".repeat(20_000);
+        let script = format!("head -c 200000 /dev/zero | tr '\\0' x\n{}", padding);
+
+        let start = Instant::now();
+        let result = execute_script_with_timeout_internal(&script, "/bin/sh", &[], Duration::from_secs(5));
+        assert!(start.elapsed() < Duration::from_secs(5), "should finish well before the timeout deadline");
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, 0);
+            assert_eq!(owned.signaled, 0);
+            rust_free_exec_result(result);
+        }
+    }
+
+    #[test]
+    fn test_execute_script_with_timeout_returns_normally_when_fast_enough() {
+        let script = "echo hi; exit 0";
+        let result = execute_script_with_timeout_internal(script, "/bin/sh", &[], Duration::from_millis(2000));
+        assert!(!result.is_null());
+
+        unsafe {
+            let owned = &*result;
+            assert_eq!(owned.exit_code, 0);
+            assert_eq!(owned.signaled, 0);
+
+            rust_free_exec_result(result);
+        }
+    }
 }
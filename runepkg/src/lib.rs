@@ -1,9 +1,12 @@
 use libc::{c_char, c_int, size_t};
 use std::ffi::{CStr, CString};
+use std::path::Path;
 use std::ptr;
+use std::sync::Mutex;
+use syntect::dumps;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{ThemeSet, Style};
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
 use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
 use once_cell::sync::Lazy;
 
@@ -11,9 +14,49 @@ use once_cell::sync::Lazy;
 pub mod exec;
 pub mod script;
 
-// Global syntax and theme sets - initialized once
-static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(|| SyntaxSet::load_defaults_newlines());
-static THEME_SET: Lazy<ThemeSet> = Lazy::new(|| ThemeSet::load_defaults());
+// Global syntax and theme sets - replaceable at runtime via rust_load_assets_from_dir
+static SYNTAX_SET: Lazy<Mutex<SyntaxSet>> = Lazy::new(|| Mutex::new(SyntaxSet::load_defaults_newlines()));
+static THEME_SET: Lazy<Mutex<ThemeSet>> = Lazy::new(|| Mutex::new(ThemeSet::load_defaults()));
+
+/// Binary cache path consulted by `rust_init_highlighting` before falling back to bundled defaults
+const DEFAULT_ASSET_DUMP_PATH: &str = "runepkg_assets.dump";
+
+/// Glob -> syntax name overrides registered via `rust_map_syntax`, consulted before extension
+/// and first-line detection so packaging scripts with nonstandard names still highlight correctly
+static SYNTAX_OVERRIDES: Lazy<Mutex<Vec<(String, String)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Minimal glob matcher supporting a single `*` wildcard (e.g. `*.install`, `PKGBUILD`)
+fn glob_matches(pattern: &str, filename: &str) -> bool {
+    match pattern.find('*') {
+        Some(idx) => {
+            let (prefix, suffix) = (&pattern[..idx], &pattern[idx + 1..]);
+            filename.len() >= prefix.len() + suffix.len()
+                && filename.starts_with(prefix)
+                && filename.ends_with(suffix)
+        }
+        None => filename == pattern,
+    }
+}
+
+/// Look up the most recently registered syntax override matching `filename`, if any
+fn syntax_override_for(filename: &str) -> Option<String> {
+    SYNTAX_OVERRIDES
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find(|(glob, _)| glob_matches(glob, filename))
+        .map(|(_, syntax_name)| syntax_name.clone())
+}
+
+/// Convert a C string pointer + length into an owned `String`
+unsafe fn c_str_to_string(ptr: *const c_char, len: c_int) -> Option<String> {
+    if ptr.is_null() || len <= 0 {
+        return None;
+    }
+    let slice = std::slice::from_raw_parts(ptr as *const u8, len as usize);
+    std::str::from_utf8(slice).ok().map(|s| s.to_string())
+}
 
 /// Highlight scheme types matching the C enum from upkg_highlight.h
 #[repr(C)]
@@ -71,23 +114,148 @@ pub unsafe extern "C" fn rust_highlight_shell_script(
     }
 }
 
+/// Color-depth-adaptive variant of `rust_highlight_shell_script` - quantizes output to match
+/// the caller's terminal (truecolor, 256-color, 16-color) or strips styling entirely for `None`
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_highlight_shell_script_mode(
+    script_content: *const c_char,
+    script_len: c_int,
+    scheme: HighlightScheme,
+    color_mode: ColorMode,
+) -> *mut c_char {
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let highlighted = highlight_script_internal_with_mode(script_str, scheme, color_mode);
+
+    match CString::new(highlighted) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
 /// Internal highlighting implementation using syntect
 /// This replaces the manual ANSI coloring from the original upkg_highlight.c
 fn highlight_script_internal(script_content: &str, scheme: HighlightScheme) -> String {
+    highlight_script_internal_with_mode(script_content, scheme, ColorMode::TrueColor)
+}
+
+/// Color depth supported by the output terminal (or lack thereof, when piping to a file)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum ColorMode {
+    TrueColor = 0,
+    Ansi256 = 1,
+    Ansi16 = 2,
+    None = 3,
+}
+
+/// Standard 16-color ANSI palette (8 normal + 8 bright), used to quantize truecolor styles
+/// down to an `Ansi16` foreground SGR code
+const ANSI16_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Quantize an RGB color to one of the 256-color cube's 6x6x6 entries by scaling each channel
+/// linearly to 0..=5 (rounded); the cube's non-uniform levels and the grayscale ramp are not considered
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let to_cube = |c: u8| -> u16 { (c as u16 * 5 + 127) / 255 };
+    16 + 36 * to_cube(r) as u8 + 6 * to_cube(g) as u8 + to_cube(b) as u8
+}
+
+/// Quantize an RGB color to the nearest of the 16 base ANSI colors, returning its foreground SGR code
+fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> u8 {
+    let mut best_index = 0;
+    let mut best_distance = u32::MAX;
+
+    for (index, &(cr, cg, cb)) in ANSI16_PALETTE.iter().enumerate() {
+        let dr = r as i32 - cr as i32;
+        let dg = g as i32 - cg as i32;
+        let db = b as i32 - cb as i32;
+        let distance = (dr * dr + dg * dg + db * db) as u32;
+        if distance < best_distance {
+            best_distance = distance;
+            best_index = index;
+        }
+    }
+
+    if best_index < 8 {
+        30 + best_index as u8
+    } else {
+        90 + (best_index - 8) as u8
+    }
+}
+
+/// Render syntect style ranges for the requested color depth - truecolor unchanged, 256/16 color
+/// quantized to the nearest palette entry, and `None` stripped of all styling
+fn style_ranges_to_string(ranges: &[(Style, &str)], color_mode: ColorMode) -> String {
+    match color_mode {
+        ColorMode::TrueColor => as_24_bit_terminal_escaped(ranges, false),
+        ColorMode::None => ranges.iter().map(|(_, text)| *text).collect(),
+        ColorMode::Ansi256 => ranges
+            .iter()
+            .map(|(style, text)| {
+                let fg = style.foreground;
+                format!("\x1b[38;5;{}m{}\x1b[0m", rgb_to_ansi256(fg.r, fg.g, fg.b), text)
+            })
+            .collect(),
+        ColorMode::Ansi16 => ranges
+            .iter()
+            .map(|(style, text)| {
+                let fg = style.foreground;
+                format!("\x1b[{}m{}\x1b[0m", rgb_to_ansi16(fg.r, fg.g, fg.b), text)
+            })
+            .collect(),
+    }
+}
+
+fn highlight_script_internal_with_mode(
+    script_content: &str,
+    scheme: HighlightScheme,
+    color_mode: ColorMode,
+) -> String {
     let theme_name = scheme_to_theme_name(scheme);
-    
+
+    let syntax_set = SYNTAX_SET.lock().unwrap();
+    let theme_set = THEME_SET.lock().unwrap();
+
     // Get syntax reference for shell scripts
-    let syntax = SYNTAX_SET
+    let syntax = syntax_set
         .find_syntax_by_extension("sh")
-        .or_else(|| SYNTAX_SET.find_syntax_by_name("Bourne Again Shell (bash)"))
-        .or_else(|| SYNTAX_SET.find_syntax_by_name("Shell Script (Bash)"))
-        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+        .or_else(|| syntax_set.find_syntax_by_name("Bourne Again Shell (bash)"))
+        .or_else(|| syntax_set.find_syntax_by_name("Shell Script (Bash)"))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
 
     // Get theme
-    let theme = THEME_SET
+    let theme = theme_set
         .themes
         .get(theme_name)
-        .unwrap_or_else(|| THEME_SET.themes.values().next().unwrap());
+        .unwrap_or_else(|| theme_set.themes.values().next().unwrap());
 
     // Create highlighter
     let mut highlighter = HighlightLines::new(syntax, theme);
@@ -96,9 +264,46 @@ fn highlight_script_internal(script_content: &str, scheme: HighlightScheme) -> S
     // Process each line
     for line in LinesWithEndings::from(script_content) {
         let ranges: Vec<(Style, &str)> = highlighter
-            .highlight_line(line, &SYNTAX_SET)
+            .highlight_line(line, &syntax_set)
+            .unwrap_or_else(|_| vec![(Style::default(), line)]);
+
+        result.push_str(&style_ranges_to_string(&ranges[..], color_mode));
+    }
+
+    result
+}
+
+/// Language-aware highlighting, picking the syntax via a registered override, the filename's
+/// extension, or (when neither matches) the content's first line - so a `#!/usr/bin/env python3`
+/// shebang selects Python even with no extension
+fn highlight_source_internal(script_content: &str, filename: &str, scheme: HighlightScheme) -> String {
+    let theme_name = scheme_to_theme_name(scheme);
+
+    let syntax_set = SYNTAX_SET.lock().unwrap();
+    let theme_set = THEME_SET.lock().unwrap();
+
+    let syntax = syntax_override_for(filename)
+        .and_then(|name| syntax_set.find_syntax_by_name(&name).cloned())
+        .or_else(|| syntax_set.find_syntax_for_file(filename).ok().flatten().cloned())
+        .or_else(|| {
+            let first_line = script_content.lines().next().unwrap_or("");
+            syntax_set.find_syntax_by_first_line(first_line).cloned()
+        })
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text().clone());
+
+    let theme = theme_set
+        .themes
+        .get(theme_name)
+        .unwrap_or_else(|| theme_set.themes.values().next().unwrap());
+
+    let mut highlighter = HighlightLines::new(&syntax, theme);
+    let mut result = String::new();
+
+    for line in LinesWithEndings::from(script_content) {
+        let ranges: Vec<(Style, &str)> = highlighter
+            .highlight_line(line, &syntax_set)
             .unwrap_or_else(|_| vec![(Style::default(), line)]);
-        
+
         let escaped = as_24_bit_terminal_escaped(&ranges[..], false);
         result.push_str(&escaped);
     }
@@ -106,6 +311,62 @@ fn highlight_script_internal(script_content: &str, scheme: HighlightScheme) -> S
     result
 }
 
+/// Highlight arbitrary source, picking the syntax from `filename` instead of always assuming shell
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_highlight_source(
+    script_content: *const c_char,
+    script_len: c_int,
+    filename: *const c_char,
+    filename_len: c_int,
+    scheme: HighlightScheme,
+) -> *mut c_char {
+    if script_content.is_null() || script_len <= 0 {
+        return ptr::null_mut();
+    }
+
+    let script_slice = std::slice::from_raw_parts(script_content as *const u8, script_len as usize);
+    let script_str = match std::str::from_utf8(script_slice) {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let filename_str = c_str_to_string(filename, filename_len).unwrap_or_default();
+    let highlighted = highlight_source_internal(script_str, &filename_str, scheme);
+
+    match CString::new(highlighted) {
+        Ok(c_string) => c_string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Register a glob -> syntax name override (e.g. `*.install` or `PKGBUILD` -> `"Bourne Again Shell (bash)"`)
+/// consulted by `rust_highlight_source` before extension/first-line detection
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw pointers from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_map_syntax(
+    glob: *const c_char,
+    glob_len: c_int,
+    syntax_name: *const c_char,
+    syntax_name_len: c_int,
+) -> c_int {
+    let glob = match c_str_to_string(glob, glob_len) {
+        Some(g) => g,
+        None => return -1,
+    };
+    let syntax_name = match c_str_to_string(syntax_name, syntax_name_len) {
+        Some(s) => s,
+        None => return -1,
+    };
+
+    SYNTAX_OVERRIDES.lock().unwrap().push((glob, syntax_name));
+    0
+}
+
 /// Free memory allocated by rust_highlight_shell_script
 /// 
 /// # Safety
@@ -123,21 +384,22 @@ pub unsafe extern "C" fn rust_free_highlighted_string(ptr: *mut c_char) {
 /// Get available themes count - useful for C code to know available options
 #[no_mangle]
 pub extern "C" fn rust_get_theme_count() -> c_int {
-    THEME_SET.themes.len() as c_int
+    THEME_SET.lock().unwrap().themes.len() as c_int
 }
 
 /// Get theme name by index - useful for C code to enumerate themes
-/// 
+///
 /// # Safety
 /// This function is unsafe because it returns a raw pointer to a C string
 /// The returned string should not be freed by the caller (it's static)
 #[no_mangle]
 pub unsafe extern "C" fn rust_get_theme_name(index: c_int) -> *const c_char {
-    if index < 0 || index >= THEME_SET.themes.len() as c_int {
+    let theme_set = THEME_SET.lock().unwrap();
+    if index < 0 || index >= theme_set.themes.len() as c_int {
         return ptr::null();
     }
-    
-    let theme_names: Vec<&String> = THEME_SET.themes.keys().collect();
+
+    let theme_names: Vec<&String> = theme_set.themes.keys().collect();
     let theme_name = theme_names[index as usize];
     
     // Convert to C string - this creates a static string that doesn't need freeing
@@ -158,14 +420,80 @@ pub extern "C" fn rust_test_ffi() -> c_int {
 }
 
 /// Initialize the highlighting system - call this once at startup
+///
+/// When a cached binary dump exists at `DEFAULT_ASSET_DUMP_PATH` (written by `rust_dump_assets`)
+/// and loads, its syntax/theme sets replace the active ones; otherwise the bundled defaults stay
+/// active. The bundled defaults are loaded in either case, since the statics initialize on first
+/// lock.
 #[no_mangle]
 pub extern "C" fn rust_init_highlighting() -> c_int {
-    // Force initialization of lazy statics
+    if Path::new(DEFAULT_ASSET_DUMP_PATH).exists() {
+        if let Ok((syntax_set, theme_set)) =
+            dumps::from_dump_file::<(SyntaxSet, ThemeSet)>(DEFAULT_ASSET_DUMP_PATH)
+        {
+            *SYNTAX_SET.lock().unwrap() = syntax_set;
+            *THEME_SET.lock().unwrap() = theme_set;
+            return 1;
+        }
+    }
+
+    // Force initialization of the lazy statics
     Lazy::force(&SYNTAX_SET);
     Lazy::force(&THEME_SET);
     1 // Success
 }
 
+/// Load custom `.sublime-syntax` files from `<dir>/syntaxes/` and `.tmTheme` files from
+/// `<dir>/themes/`, replacing the active syntax and theme sets
+///
+/// # Safety
+/// This function is unsafe because it dereferences a raw pointer from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_load_assets_from_dir(path: *const c_char, path_len: c_int) -> c_int {
+    let base = match c_str_to_string(path, path_len) {
+        Some(p) => p,
+        None => return -1,
+    };
+
+    let mut builder = SyntaxSetBuilder::new();
+    builder.add_plain_text_syntax();
+    if builder.add_from_folder(Path::new(&base).join("syntaxes"), true).is_err() {
+        return -1;
+    }
+    let new_syntax_set = builder.build();
+
+    let mut new_theme_set = ThemeSet::load_defaults();
+    if new_theme_set.add_from_folder(Path::new(&base).join("themes")).is_err() {
+        return -1;
+    }
+
+    *SYNTAX_SET.lock().unwrap() = new_syntax_set;
+    *THEME_SET.lock().unwrap() = new_theme_set;
+    0
+}
+
+/// Serialize the active syntax and theme sets to a binary cache file at `cache_path`, so custom
+/// definitions need not be re-parsed; `rust_init_highlighting` reads the dump only from
+/// `DEFAULT_ASSET_DUMP_PATH`
+///
+/// # Safety
+/// This function is unsafe because it dereferences a raw pointer from C
+#[no_mangle]
+pub unsafe extern "C" fn rust_dump_assets(cache_path: *const c_char, path_len: c_int) -> c_int {
+    let path = match c_str_to_string(cache_path, path_len) {
+        Some(p) => p,
+        None => return -1,
+    };
+
+    let syntax_set = SYNTAX_SET.lock().unwrap().clone();
+    let theme_set = THEME_SET.lock().unwrap().clone();
+
+    match dumps::dump_to_file(&(syntax_set, theme_set), &path) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// Get version information
 #[no_mangle]
 pub unsafe extern "C" fn rust_get_version() -> *const c_char {